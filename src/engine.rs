@@ -0,0 +1,4 @@
+pub mod amount;
+pub mod client;
+pub mod error;
+pub mod transaction_engine;
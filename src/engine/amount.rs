@@ -0,0 +1,107 @@
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// number of ten-thousandths of a unit in one whole unit
+const SCALE: i64 = 10_000;
+
+///
+/// Fixed-point monetary amount, stored as the number of ten-thousandths of a
+/// unit. Using an integer instead of `f32`/`f64` keeps deposits, disputes and
+/// withdrawals exact instead of accumulating rounding error.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    ///
+    /// `None` on overflow/underflow rather than wrapping or panicking, so a
+    /// pathologically large balance surfaces as a `LedgerError` instead of
+    /// silently corrupting the account it touches
+    ///
+    pub fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_add(rhs.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_sub(rhs.0).map(Amount)
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        let negative = trimmed.starts_with('-');
+        let unsigned = trimmed.strip_prefix('-').unwrap_or(trimmed);
+
+        let mut parts = unsigned.splitn(2, '.');
+        let whole_part = parts.next().unwrap_or("0");
+        let frac_part = parts.next().unwrap_or("");
+
+        if frac_part.len() > 4 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("amount '{value}' has more than four fractional digits"),
+            ));
+        }
+
+        let whole: i64 = whole_part
+            .parse()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, format!("invalid amount '{value}'")))?;
+        let mut frac_digits = frac_part.to_string();
+        while frac_digits.len() < 4 {
+            frac_digits.push('0');
+        }
+        let frac: i64 = frac_digits
+            .parse()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, format!("invalid amount '{value}'")))?;
+
+        let scaled = whole * SCALE + frac;
+        Ok(Amount(if negative { -scaled } else { scaled }))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = self.0.unsigned_abs();
+        let whole = magnitude / SCALE as u64;
+        let frac = magnitude % SCALE as u64;
+
+        if self.0 < 0 {
+            write!(f, "-")?;
+        }
+
+        if frac == 0 {
+            write!(f, "{whole}")
+        } else {
+            let mut frac_str = format!("{frac:04}");
+            while frac_str.ends_with('0') {
+                frac_str.pop();
+            }
+            write!(f, "{whole}.{frac_str}")
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Amount::from_str(&value).map_err(serde::de::Error::custom)
+    }
+}
+
@@ -1,10 +1,14 @@
-use serde::Deserialize;
-use std::{collections::HashMap, io::Error};
+use std::{
+    collections::HashMap,
+    io::{Error, Write},
+};
+
+use super::{amount::Amount, error::LedgerError};
 
 ///
 /// transaction type
 ///
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum TransactionType {
     Deposit = 0,
     Withdrawal = 1,
@@ -13,20 +17,17 @@ pub enum TransactionType {
     Chargeback = 4,
 }
 
-impl From<String> for TransactionType {
-    fn from(value: String) -> Self {
-        if value == "deposit" {
-            TransactionType::Deposit
-        } else if value == "withdrawal" {
-            TransactionType::Withdrawal
-        } else if value == "resolve" {
-            TransactionType::Resolve
-        } else if value == "chargeback" {
-            TransactionType::Chargeback
-        } else {
-            TransactionType::Dispute
-        }
-    }
+///
+/// lifecycle of a processed transaction. Disputes/resolves/chargebacks are
+/// transitions on this state rather than rewrites of the transaction type,
+/// so the original deposit/withdrawal record is never lost.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxState {
+    Processed,
+    Disputed,
+    Resolved,
+    ChargedBack,
 }
 
 ///
@@ -34,35 +35,34 @@ impl From<String> for TransactionType {
 ///
 pub struct Client {
     /*id of the client*/
-    _id: u16,
+    id: u16,
     /*The total funds that are available for trading, staking, withdrawal, etc. This should be equal to the total - held amounts*/
-    available: f32,
+    available: Amount,
     /*The total funds that are held for dispute. This should be equal to total - available amounts*/
-    held: f32,
+    held: Amount,
     /*The total funds that are available or held. This should be equal to available + held*/
-    total: f64,
+    total: Amount,
     /*Whether the account is locked. An account is locked if a charge back occurs*/
     locked: bool,
-    /* all transaction performed by this client needed in case of dispute/resolved/charge-back transaction id : (transaction type, amount)*/
-    transactions: HashMap<u32, (TransactionType, f32)>,
+    /* all transaction performed by this client needed in case of dispute/resolved/charge-back transaction id : (transaction type, amount, state)*/
+    transactions: HashMap<u32, (TransactionType, Amount, TxState)>,
 }
 
 impl Client {
-    pub fn new(id: u16, tx_id: u32, transaction_type: TransactionType, amount: f32) -> Self {
-        let total = match transaction_type {
-            TransactionType::Deposit => amount as f64,
-            _ => 0f64,
-        };
-
-        let mut transaction = HashMap::new();
-        transaction.insert(tx_id, (transaction_type, amount));
+    ///
+    /// a fresh client with zero balances and no transaction history; the
+    /// first transaction routed to it still goes through `execute_transaction`
+    /// like any other, so e.g. a withdrawal or dispute can't be the first
+    /// thing a client id ever sees without tripping the usual checks
+    ///
+    pub fn new(id: u16) -> Self {
         Client {
-            _id: id,
-            available: amount,
-            held: 0f32,
-            total,
+            id,
+            available: Amount::ZERO,
+            held: Amount::ZERO,
+            total: Amount::ZERO,
             locked: false,
-            transactions: transaction,
+            transactions: HashMap::new(),
         }
     }
 
@@ -70,11 +70,18 @@ impl Client {
         &mut self,
         tx_id: u32,
         tx_type: TransactionType,
-        amount: f32,
-    ) -> Result<(), Error> {
-        self.available += amount;
-        self.total = (self.available + self.held) as f64;
-        self.transactions.insert(tx_id, (tx_type, amount));
+        amount: Amount,
+    ) -> Result<(), LedgerError> {
+        self.available = self
+            .available
+            .checked_add(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.total = self
+            .available
+            .checked_add(self.held)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.transactions
+            .insert(tx_id, (tx_type, amount, TxState::Processed));
         Ok(())
     }
 
@@ -85,24 +92,45 @@ impl Client {
         &mut self,
         tx_id: u32,
         tx_type: TransactionType,
-        amount: f32,
-    ) -> Result<(), Error> {
-        if self.available > amount && !self.locked {
-            self.available -= amount;
-            self.total = (self.available + self.held) as f64;
-            self.transactions.insert(tx_id, (tx_type, amount));
+        amount: Amount,
+    ) -> Result<(), LedgerError> {
+        if self.locked {
+            return Err(LedgerError::FrozenAccount(self.id));
         }
+        if self.available < amount {
+            return Err(LedgerError::NotEnoughFunds(self.id));
+        }
+        self.available = self
+            .available
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.total = self
+            .available
+            .checked_add(self.held)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.transactions
+            .insert(tx_id, (tx_type, amount, TxState::Processed));
         Ok(())
     }
 
-    fn perform_dispute(&mut self, tx_id: u32) -> Result<(), Error> {
-        if self.transactions.contains_key(&tx_id) {
-            let tx = self.transactions.get(&tx_id).unwrap();
-            let amount = tx.1;
-            self.available -= tx.1;
-            self.held += tx.1;
-            self.transactions
-                .insert(tx_id, (TransactionType::Dispute, amount));
+    fn perform_dispute(&mut self, tx_id: u32) -> Result<(), LedgerError> {
+        let (amount, state) = match self.transactions.get(&tx_id) {
+            Some((_, amount, state)) => (*amount, *state),
+            None => return Err(LedgerError::UnknownTx(self.id, tx_id)),
+        };
+        if state != TxState::Processed {
+            return Err(LedgerError::AlreadyDisputed(tx_id));
+        }
+        self.available = self
+            .available
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.held = self
+            .held
+            .checked_add(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        if let Some(tx) = self.transactions.get_mut(&tx_id) {
+            tx.2 = TxState::Disputed;
         }
         Ok(())
     }
@@ -110,34 +138,53 @@ impl Client {
     ///
     /// resolve should be applied for disputed and non frozen transactions
     ///
-    fn perform_resolve(&mut self, tx_id: u32) -> Result<(), Error> {
-        if self.transactions.contains_key(&tx_id) {
-            let tx = self.transactions.get(&tx_id).unwrap();
-            let amount = tx.1;
-            if tx.0 == TransactionType::Dispute && !self.locked {
-                self.available += tx.1;
-                self.held -= tx.1;
-                self.transactions
-                    .insert(tx_id, (TransactionType::Resolve, amount));
-            }
+    fn perform_resolve(&mut self, tx_id: u32) -> Result<(), LedgerError> {
+        let (amount, state) = match self.transactions.get(&tx_id) {
+            Some((_, amount, state)) => (*amount, *state),
+            None => return Err(LedgerError::UnknownTx(self.id, tx_id)),
+        };
+        if state != TxState::Disputed {
+            return Err(LedgerError::NotDisputed(tx_id));
+        }
+        if self.locked {
+            return Err(LedgerError::FrozenAccount(self.id));
+        }
+        self.available = self
+            .available
+            .checked_add(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.held = self
+            .held
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        if let Some(tx) = self.transactions.get_mut(&tx_id) {
+            tx.2 = TxState::Resolved;
         }
         Ok(())
     }
 
     ///
-    /// Chargeback should be applied after resolved
+    /// Chargeback should only be applied to a disputed transaction
     ///
-    fn perform_chargeback(&mut self, tx_id: u32) -> Result<(), Error> {
-        if self.transactions.contains_key(&tx_id) {
-            let tx = self.transactions.get(&tx_id).unwrap();
-            let amount = tx.1;
-            if tx.0 == TransactionType::Dispute {
-                self.held -= tx.1;
-                self.total = (self.available + self.held) as f64;
-                self.locked = true;
-                self.transactions
-                    .insert(tx_id, (TransactionType::Chargeback, amount));
-            }
+    fn perform_chargeback(&mut self, tx_id: u32) -> Result<(), LedgerError> {
+        let (amount, state) = match self.transactions.get(&tx_id) {
+            Some((_, amount, state)) => (*amount, *state),
+            None => return Err(LedgerError::UnknownTx(self.id, tx_id)),
+        };
+        if state != TxState::Disputed {
+            return Err(LedgerError::NotDisputed(tx_id));
+        }
+        self.held = self
+            .held
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.total = self
+            .available
+            .checked_add(self.held)
+            .ok_or(LedgerError::AmountOverflow(self.id, tx_id))?;
+        self.locked = true;
+        if let Some(tx) = self.transactions.get_mut(&tx_id) {
+            tx.2 = TxState::ChargedBack;
         }
         Ok(())
     }
@@ -146,8 +193,8 @@ impl Client {
         &mut self,
         tx_id: u32,
         transaction_type: TransactionType,
-        amount: f32,
-    ) -> Result<(), Error> {
+        amount: Amount,
+    ) -> Result<(), LedgerError> {
         match transaction_type {
             TransactionType::Deposit => self.perform_deposit(tx_id, transaction_type, amount)?,
             TransactionType::Withdrawal => {
@@ -160,11 +207,17 @@ impl Client {
         Ok(())
     }
 
-    pub fn show_info(&mut self) {
-        print!("{},", self._id);
-        print!("{},", self.available);
-        print!("{},", self.held);
-        print!("{},", self.total);
-        print!("{}", self.locked);
+    ///
+    /// writes this client's balances as one CSV row
+    ///
+    pub fn write_row<W: Write>(&self, writer: &mut csv::Writer<W>) -> Result<(), Error> {
+        writer.write_record([
+            self.id.to_string(),
+            self.available.to_string(),
+            self.held.to_string(),
+            self.total.to_string(),
+            self.locked.to_string(),
+        ])?;
+        Ok(())
     }
 }
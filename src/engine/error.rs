@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+///
+/// business-rule and parse-level failures raised while processing a single
+/// transaction. One bad record should never abort the whole input stream,
+/// so callers are expected to log these and move on to the next record.
+///
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("client {0} does not have enough available funds")]
+    NotEnoughFunds(u16),
+    #[error("client {0} has no transaction {1}")]
+    UnknownTx(u16, u32),
+    #[error("transaction {0} is already disputed")]
+    AlreadyDisputed(u32),
+    #[error("transaction {0} is not disputed")]
+    NotDisputed(u32),
+    #[error("client {0} account is frozen")]
+    FrozenAccount(u16),
+    #[error("client {0} balance overflowed applying transaction {1}")]
+    AmountOverflow(u16, u32),
+    #[error("record is missing an amount")]
+    MissingAmount,
+    #[error("deposit/withdrawal amount must not be negative")]
+    NegativeAmount,
+    #[error("dispute/resolve/chargeback rows must not carry an amount")]
+    UnexpectedAmount,
+    #[error("record is missing a client id")]
+    MissingClient,
+    #[error("record is missing a transaction id")]
+    MissingTx,
+    #[error("unknown transaction type '{0}'")]
+    UnknownTransactionType(String),
+}
@@ -1,15 +1,135 @@
-use std::{collections::HashMap, env, io::Error};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fs::File,
+    io::{Error, Write},
+    sync::mpsc,
+    thread,
+};
 
+use csv::Trim;
 use serde::Deserialize;
 
-use super::client::Client;
+use super::{
+    amount::Amount,
+    client::{Client, TransactionType},
+    error::LedgerError,
+};
 
+///
+/// raw shape of a CSV row, deserialized before the `type` column is validated
+///
 #[derive(Debug, Deserialize)]
-pub struct Record {
+pub struct TransactionRecord {
     r#type: String,
     client: Option<u16>,
     tx: Option<u32>,
-    amount: Option<f32>,
+    amount: Option<Amount>,
+}
+
+///
+/// a validated transaction: the `type` column has been checked against the
+/// known set and deposit/withdrawal rows are guaranteed to carry an amount
+///
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "TransactionRecord")]
+pub enum Transaction {
+    Deposit {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Withdrawal {
+        client: u16,
+        tx: u32,
+        amount: Amount,
+    },
+    Dispute {
+        client: u16,
+        tx: u32,
+    },
+    Resolve {
+        client: u16,
+        tx: u32,
+    },
+    Chargeback {
+        client: u16,
+        tx: u32,
+    },
+}
+
+impl Transaction {
+    /// the client every variant is routed by when sharding across workers
+    fn client_id(&self) -> u16 {
+        match self {
+            Transaction::Deposit { client, .. }
+            | Transaction::Withdrawal { client, .. }
+            | Transaction::Dispute { client, .. }
+            | Transaction::Resolve { client, .. }
+            | Transaction::Chargeback { client, .. } => *client,
+        }
+    }
+}
+
+impl TryFrom<TransactionRecord> for Transaction {
+    type Error = LedgerError;
+
+    fn try_from(record: TransactionRecord) -> Result<Self, Self::Error> {
+        let TransactionRecord {
+            r#type,
+            client,
+            tx,
+            amount,
+        } = record;
+        let client = client.ok_or(LedgerError::MissingClient)?;
+        let tx = tx.ok_or(LedgerError::MissingTx)?;
+        match r#type.as_str() {
+            "deposit" => {
+                let amount = amount.ok_or(LedgerError::MissingAmount)?;
+                if amount.is_negative() {
+                    return Err(LedgerError::NegativeAmount);
+                }
+                Ok(Transaction::Deposit { client, tx, amount })
+            }
+            "withdrawal" => {
+                let amount = amount.ok_or(LedgerError::MissingAmount)?;
+                if amount.is_negative() {
+                    return Err(LedgerError::NegativeAmount);
+                }
+                Ok(Transaction::Withdrawal { client, tx, amount })
+            }
+            "dispute" => {
+                if amount.is_some() {
+                    return Err(LedgerError::UnexpectedAmount);
+                }
+                Ok(Transaction::Dispute { client, tx })
+            }
+            "resolve" => {
+                if amount.is_some() {
+                    return Err(LedgerError::UnexpectedAmount);
+                }
+                Ok(Transaction::Resolve { client, tx })
+            }
+            "chargeback" => {
+                if amount.is_some() {
+                    return Err(LedgerError::UnexpectedAmount);
+                }
+                Ok(Transaction::Chargeback { client, tx })
+            }
+            other => Err(LedgerError::UnknownTransactionType(other.to_string())),
+        }
+    }
+}
+
+///
+/// `has_headers` treats the first row as the header, `trim` tolerates
+/// whitespace around fields and `flexible`
+/// lets dispute/resolve/chargeback rows omit the trailing amount column
+///
+fn configured_csv_reader_builder() -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder.has_headers(true).trim(Trim::All).flexible(true);
+    builder
 }
 
 ///
@@ -27,45 +147,130 @@ impl TransactionEngine {
         }
     }
 
-    pub fn process_transactions(&mut self, record: Record) -> Result<(), Error> {
-        let transaction_type = record.r#type;
-        let client_id = record.client.unwrap();
-        let tx_id = record.tx.unwrap();
-        let amount = record.amount.unwrap_or_default();
-        let transaction_type = transaction_type.into();
-        if let std::collections::hash_map::Entry::Vacant(e) = self.clients.entry(client_id) {
-            let client = Client::new(client_id, tx_id, transaction_type, amount);
-            e.insert(client);
-        } else {
-            self.clients
-                .get_mut(&client_id)
-                .unwrap()
-                .execute_transaction(tx_id, transaction_type, amount)?;
-        }
+    pub fn process_transactions(&mut self, transaction: Transaction) -> Result<(), LedgerError> {
+        let (client_id, tx_id, transaction_type, amount) = match transaction {
+            Transaction::Deposit { client, tx, amount } => {
+                (client, tx, TransactionType::Deposit, amount)
+            }
+            Transaction::Withdrawal { client, tx, amount } => {
+                (client, tx, TransactionType::Withdrawal, amount)
+            }
+            Transaction::Dispute { client, tx } => {
+                (client, tx, TransactionType::Dispute, Amount::ZERO)
+            }
+            Transaction::Resolve { client, tx } => {
+                (client, tx, TransactionType::Resolve, Amount::ZERO)
+            }
+            Transaction::Chargeback { client, tx } => {
+                (client, tx, TransactionType::Chargeback, Amount::ZERO)
+            }
+        };
+        self.clients
+            .entry(client_id)
+            .or_insert_with(|| Client::new(client_id))
+            .execute_transaction(tx_id, transaction_type, amount)?;
         Ok(())
     }
 
-    pub fn read_input(&mut self) -> Result<(), Error> {
+    fn process_and_log(&mut self, transaction: Transaction) {
+        if let Err(e) = self.process_transactions(transaction) {
+            eprintln!("skipping transaction: {e}");
+        }
+    }
+
+    ///
+    /// partitions transactions by `client id % thread_count` and hands each
+    /// partition to its own worker thread over a channel. Every transaction
+    /// touches exactly one client, so each worker's `TransactionEngine` never
+    /// shares state with the others and no locking is needed; the per-shard
+    /// client maps are merged back into `self` once all workers finish.
+    ///
+    fn process_sharded(&mut self, mut rdr: csv::Reader<File>, thread_count: usize) {
+        let (senders, handles): (Vec<_>, Vec<_>) = (0..thread_count)
+            .map(|_| {
+                let (tx, rx) = mpsc::channel::<Transaction>();
+                let handle = thread::spawn(move || {
+                    let mut shard = TransactionEngine::new();
+                    for transaction in rx {
+                        shard.process_and_log(transaction);
+                    }
+                    shard.clients
+                });
+                (tx, handle)
+            })
+            .unzip();
+
+        for result in rdr.deserialize() {
+            let transaction: Transaction = match result {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    eprintln!("skipping malformed record: {e}");
+                    continue;
+                }
+            };
+            let shard = transaction.client_id() as usize % thread_count;
+            let _ = senders[shard].send(transaction);
+        }
+        drop(senders);
+
+        for handle in handles {
+            match handle.join() {
+                Ok(clients) => self.clients.extend(clients),
+                Err(e) => {
+                    eprintln!("worker thread panicked, its clients are missing from output: {e:?}")
+                }
+            }
+        }
+    }
+
+    pub fn read_input<W: Write>(&mut self, writer: &mut csv::Writer<W>) -> Result<(), Error> {
         let args: Vec<String> = env::args().collect();
         if args.len() > 1 {
-            let mut rdr = csv::Reader::from_path(&args[1])?;
+            let thread_count = args
+                .get(2)
+                .and_then(|count| count.parse::<usize>().ok())
+                .filter(|count| *count > 0)
+                .unwrap_or(1);
+            let mut rdr = configured_csv_reader_builder().from_path(&args[1])?;
             let _header = rdr.headers()?;
-            for result in rdr.deserialize() {
-                let record: Record = result?;
-                self.process_transactions(record)?;
+            if thread_count > 1 {
+                self.process_sharded(rdr, thread_count);
+            } else {
+                for result in rdr.deserialize() {
+                    let transaction: Transaction = match result {
+                        Ok(transaction) => transaction,
+                        Err(e) => {
+                            eprintln!("skipping malformed record: {e}");
+                            continue;
+                        }
+                    };
+                    self.process_and_log(transaction);
+                }
             }
-            self.display_result();
+            self.dump_csv(writer)?;
         } else {
             println!("input csv file not found!");
         }
         Ok(())
     }
 
-    pub fn display_result(&mut self) {
-        println!("client,available,held,total,locked");
-        for client in self.clients.values_mut() {
-            client.show_info();
+    ///
+    /// writes accounts in ascending client-id order, independent of the
+    /// `clients` map's hash iteration order, so output diffs stay stable
+    /// across runs
+    ///
+    pub fn dump_csv<W: Write>(&self, writer: &mut csv::Writer<W>) -> Result<(), Error> {
+        let ordered: BTreeMap<u16, &Client> = self
+            .clients
+            .iter()
+            .map(|(id, client)| (*id, client))
+            .collect();
+        writer.write_record(["client", "available", "held", "total", "locked"])?;
+        for client in ordered.values() {
+            client.write_row(writer)?;
         }
+        writer.flush()?;
+        Ok(())
     }
 }
 
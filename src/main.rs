@@ -2,7 +2,8 @@ mod engine;
 use crate::engine::transaction_engine::TransactionEngine;
 fn main() {
     let mut engine = TransactionEngine::new();
-    let res = engine.read_input();
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    let res = engine.read_input(&mut writer);
     if let Some(e) = res.err() {
         println!("{:?}", e.to_string());
     }